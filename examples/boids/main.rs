@@ -6,7 +6,12 @@ extern crate rand;
 #[path = "../framework.rs"]
 mod framework;
 
+#[path = "../particle_system.rs"]
+mod particle_system;
+
+use particle_system::{ParticleSystem, ParticleSystemDescriptor};
 use zerocopy::{AsBytes};
+use std::time::Instant;
 
 
 // number of boid particles to simulate
@@ -17,16 +22,24 @@ const NUM_PARTICLES: u32 = 1500;
 
 const PARTICLES_PER_GROUP: u32 = 64;
 
+// vertex layout of a single particle instance: position then velocity
+
+const PARTICLE_ATTRIBS: [wgpu::VertexAttributeDescriptor; 2] =
+    wgpu::vertex_attr_array![0 => Float2, 1 => Float2];
+
+// vertex layout of the three shared instance-local triangle vertices
+
+const VERTEX_ATTRIBS: [wgpu::VertexAttributeDescriptor; 1] =
+    wgpu::vertex_attr_array![2 => Float2];
+
 
 /// Example struct holds references to wgpu resources and frame persistent data
 struct Example {
-    particle_bind_groups: Vec<wgpu::BindGroup>,
-    particle_buffers: Vec<wgpu::Buffer>,
+    particles: ParticleSystem,
     vertices_buffer: wgpu::Buffer,
-    compute_pipeline: wgpu::ComputePipeline,
     render_pipeline: wgpu::RenderPipeline,
-    work_group_count: u32,
-    frame_num: usize,
+    last_frame: Instant,
+    last_frame_time_ms: f32,
 }
 
 
@@ -53,36 +66,50 @@ impl framework::Example for Example {
         let boids = framework::load_glsl(&boids_source_str, framework::ShaderStage::Compute);
         let boids_module = device.create_shader_module(&boids);
 
-        let vs = framework::load_glsl(include_str!("shader.vert"), framework::ShaderStage::Vertex);
-        let vs_module = device.create_shader_module(&vs);
+        // precompiled offline, so this one shader no longer round-trips through shaderc at startup
+        let vs_module = device.create_shader_module(&wgpu::include_spirv!("shader.vert.spv"));
 
         let fs = framework::load_glsl(include_str!("shader.frag"), framework::ShaderStage::Fragment);
         let fs_module = device.create_shader_module(&fs);
 
 
-        // create compute bind layout group and compute pipeline layout
+        // flocking rule constants, uploaded as the compute kernel's config uniform
 
-        let compute_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            bindings: &[
-                wgpu::BindGroupLayoutBinding {
-                    binding: 0,
-                    visibility: wgpu::ShaderStage::COMPUTE,
-                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-                },
-                wgpu::BindGroupLayoutBinding {
-                    binding: 1,
-                    visibility: wgpu::ShaderStage::COMPUTE,
-                    ty: wgpu::BindingType::StorageBuffer { dynamic: false, readonly: false },
-                },
-                wgpu::BindGroupLayoutBinding {
-                    binding: 2,
-                    visibility: wgpu::ShaderStage::COMPUTE,
-                    ty: wgpu::BindingType::StorageBuffer { dynamic: false, readonly: false },
-                },
-            ],
-        });
-        let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            bind_group_layouts: &[&compute_bind_group_layout],
+        let sim_param_data = [
+            0.04f32, // deltaT
+            0.1,     // rule1Distance
+            0.025,   // rule2Distance
+            0.025,   // rule3Distance
+            0.02,    // rule1Scale
+            0.05,    // rule2Scale
+            0.005    // rule3Scale
+        ].to_vec();
+
+
+        // buffer for all particles data of type [(posx,posy,velx,vely),...]
+
+        let mut initial_particle_data = vec![0.0f32; (4 * NUM_PARTICLES) as usize];
+        for particle_instance_chunk in initial_particle_data.chunks_mut(4) {
+            particle_instance_chunk[0] = 2.0 * (rand::random::<f32>() - 0.5); // posx
+            particle_instance_chunk[1] = 2.0 * (rand::random::<f32>() - 0.5); // posy
+            particle_instance_chunk[2] = 2.0 * (rand::random::<f32>() - 0.5) * 0.1; // velx
+            particle_instance_chunk[3] = 2.0 * (rand::random::<f32>() - 0.5) * 0.1; // vely
+        }
+
+
+        // the ParticleSystem owns the double-buffered particle storage, the
+        // compute bind groups built around the boids kernel, and the dispatch;
+        // it's built before the render pipeline so its instance layout can
+        // feed the render pipeline's vertex_buffers below
+
+        let particles = ParticleSystem::new(device, &ParticleSystemDescriptor {
+            particle_count: NUM_PARTICLES,
+            particles_per_group: PARTICLES_PER_GROUP,
+            compute_module: &boids_module,
+            config_data: sim_param_data.as_bytes(),
+            initial_particle_data: initial_particle_data.as_bytes(),
+            instance_attributes: &PARTICLE_ATTRIBS,
+            instance_stride: wgpu::util::vertex_stride(&[wgpu::VertexFormat::Float2, wgpu::VertexFormat::Float2]),
         });
 
 
@@ -119,35 +146,11 @@ impl framework::Example for Example {
             depth_stencil_state: None,
             index_format: wgpu::IndexFormat::Uint16,
             vertex_buffers: &[
+                particles.instance_buffer_descriptor(),
                 wgpu::VertexBufferDescriptor {
-                    stride: 4 * 4,
-                    step_mode: wgpu::InputStepMode::Instance,
-                    attributes: &[
-                        // instance position
-                        wgpu::VertexAttributeDescriptor {
-                            offset: 0,
-                            format: wgpu::VertexFormat::Float2,
-                            shader_location: 0,
-                        },
-                        // instance velocity
-                        wgpu::VertexAttributeDescriptor {
-                            offset: 2 * 4,
-                            format: wgpu::VertexFormat::Float2,
-                            shader_location: 1,
-                        },
-                    ]
-                },
-                wgpu::VertexBufferDescriptor {
-                    stride: 2 * 4,
+                    stride: wgpu::util::vertex_stride(&[wgpu::VertexFormat::Float2]),
                     step_mode: wgpu::InputStepMode::Vertex,
-                    attributes: &[
-                        // vertex positions
-                        wgpu::VertexAttributeDescriptor {
-                            offset: 0,
-                            format: wgpu::VertexFormat::Float2,
-                            shader_location: 2,
-                        },
-                    ]
+                    attributes: &VERTEX_ATTRIBS,
                 },
             ],
             sample_count: 1,
@@ -156,114 +159,21 @@ impl framework::Example for Example {
         });
 
 
-        // create compute pipeline
-
-        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            layout: &compute_pipeline_layout,
-            compute_stage: wgpu::ProgrammableStageDescriptor {
-                module: &boids_module,
-                entry_point: "main",
-            },
-        });
-
-        
         // buffer for the three 2d triangle vertices of each instance
 
         let vertex_buffer_data = [-0.01f32, -0.02, 0.01, -0.02, 0.00, 0.02];
-        let vertices_buffer = device.create_buffer_with_data(vertex_buffer_data.as_bytes(), 
+        let vertices_buffer = device.create_buffer_with_data(vertex_buffer_data.as_bytes(),
             wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST);
 
 
-        // buffer for simulation parameters uniform
-
-        let sim_param_data = [
-            0.04f32, // deltaT
-            0.1,     // rule1Distance
-            0.025,   // rule2Distance
-            0.025,   // rule3Distance
-            0.02,    // rule1Scale
-            0.05,    // rule2Scale
-            0.005    // rule3Scale
-        ].to_vec();
-        let sim_param_buffer = device.create_buffer_with_data(sim_param_data.as_bytes(), 
-            wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST);
-
-
-        // buffer for all particles data of type [(posx,posy,velx,vely),...]
-
-        let mut initial_particle_data = vec![0.0f32; (4 * NUM_PARTICLES) as usize];
-        for particle_instance_chunk in initial_particle_data.chunks_mut(4) {
-            particle_instance_chunk[0] = 2.0 * (rand::random::<f32>() - 0.5); // posx
-            particle_instance_chunk[1] = 2.0 * (rand::random::<f32>() - 0.5); // posy
-            particle_instance_chunk[2] = 2.0 * (rand::random::<f32>() - 0.5) * 0.1; // velx
-            particle_instance_chunk[3] = 2.0 * (rand::random::<f32>() - 0.5) * 0.1; // vely
-        }
-
-
-        // creates two buffers of particle data each of size NUM_PARTICLES
-        // the two buffers alternate as dst and src for each frame
-
-        let mut particle_buffers = Vec::<wgpu::Buffer>::new();
-        let mut particle_bind_groups = Vec::<wgpu::BindGroup>::new();
-        for _i in 0..2 {
-            particle_buffers.push(
-                device.create_buffer_with_data(initial_particle_data.as_bytes(), wgpu::BufferUsage::VERTEX
-                    | wgpu::BufferUsage::STORAGE
-                    | wgpu::BufferUsage::COPY_DST)
-            );
-        }
-
-
-        // create two bind groups, one for each buffer as the src
-        // where the alternate buffer is used as the dst
-
-        for i in 0..2 {
-            particle_bind_groups.push(
-                device.create_bind_group(
-                    &wgpu::BindGroupDescriptor {
-                        layout: &compute_bind_group_layout,
-                        bindings: &[
-                            wgpu::Binding {
-                                binding: 0,
-                                resource: wgpu::BindingResource::Buffer {
-                                    buffer: &sim_param_buffer,
-                                    range: 0 .. (4 * sim_param_data.len() as u64), // 4 = size_of f32
-                                },
-                            },
-                            wgpu::Binding {
-                                binding: 1,
-                                resource: wgpu::BindingResource::Buffer {
-                                    buffer: &particle_buffers[i],
-                                    range: 0 .. (4 * initial_particle_data.len() as u64), // 4 = size_of f32
-                                },
-                            },
-                            wgpu::Binding {
-                                binding: 2,
-                                resource: wgpu::BindingResource::Buffer {
-                                    buffer: &particle_buffers[(i + 1) % 2], // bind to opposite buffer
-                                    range: 0 .. (4 * initial_particle_data.len() as u64), // 4 = size_of f32
-                                },
-                            },
-                        ],
-                    }
-                )
-            );
-        }
-
-        // calculates number of work groups from PARTICLES_PER_GROUP constant
-        let work_group_count = ((NUM_PARTICLES as f32) / (PARTICLES_PER_GROUP as f32)).ceil() as u32;
-
-
         // returns Example struct and No encoder commands
 
         (Example {
-            particle_bind_groups,
-            particle_buffers,
+            particles,
             vertices_buffer,
-            compute_pipeline,
             render_pipeline,
-            work_group_count,
-            frame_num: 0,
+            last_frame: Instant::now(),
+            last_frame_time_ms: 0.0,
         }, None)
     }
 
@@ -288,8 +198,14 @@ impl framework::Example for Example {
         &mut self,
         frame: &wgpu::SwapChainOutput,
         device: &wgpu::Device,
+        staging_belt: &mut wgpu::util::StagingBelt,
     ) -> wgpu::CommandBuffer {
 
+        // track frame time for the HUD, reset for the next frame
+        let now = Instant::now();
+        self.last_frame_time_ms = (now - self.last_frame).as_secs_f32() * 1000.0;
+        self.last_frame = now;
+
         // create render pass descriptor
         let render_pass_descriptor = wgpu::RenderPassDescriptor {
             color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
@@ -305,33 +221,48 @@ impl framework::Example for Example {
         // get command encoder
         let mut command_encoder =
             device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
-        
-        {
-            // compute pass
-            let mut cpass = command_encoder.begin_compute_pass();
-            cpass.set_pipeline(&self.compute_pipeline);
-            cpass.set_bind_group(0, &self.particle_bind_groups[self.frame_num % 2], &[]);
-            cpass.dispatch(self.work_group_count, 1, 1);
-        }
+
+        // re-upload the flocking rule constants through the staging belt each
+        // frame, animating deltaT with the real (rather than the fixed 0.04
+        // assumed at startup) time since the last frame
+        let sim_param_data = [
+            (self.last_frame_time_ms / 1000.0).min(0.04), // deltaT, clamped so a stall doesn't blow up the sim
+            0.1,     // rule1Distance
+            0.025,   // rule2Distance
+            0.025,   // rule3Distance
+            0.02,    // rule1Scale
+            0.05,    // rule2Scale
+            0.005    // rule3Scale
+        ];
+        self.particles.set_config(staging_belt, &mut command_encoder, device, sim_param_data.as_bytes());
+
+        // advance the flocking simulation one step, ping-ponging particle storage
+        self.particles.dispatch(&mut command_encoder);
 
         {
             // render pass
             let mut rpass = command_encoder.begin_render_pass(&render_pass_descriptor);
             rpass.set_pipeline(&self.render_pipeline);
             rpass.set_vertex_buffers(0, &[
-                (&self.particle_buffers[(self.frame_num + 1) % 2], 0), // render dst particles
+                (self.particles.instance_buffer(), 0), // the just-simulated particles
                 (&self.vertices_buffer, 0), // the three instance-local vertices
             ]);
-            rpass.draw(0..3, 0..NUM_PARTICLES);
+            rpass.draw(0..3, 0..self.particles.particle_count());
         }
 
-        // update frame count
-        self.frame_num += 1;
-
         // done
         command_encoder.finish()
     }
 
+    /// live particle count / frame time HUD, composited by the framework
+    fn hud_text(&self) -> Option<String> {
+        Some(format!(
+            "{} particles | {:.2} ms/frame",
+            self.particles.particle_count(),
+            self.last_frame_time_ms
+        ))
+    }
+
 }
 
 
@@ -0,0 +1,182 @@
+// Shared example bootstrap: window/surface/swap chain setup, the main event
+// loop, and GLSL-at-runtime shader compilation via shaderc. Every example
+// binary in this directory implements `Example` and calls `run::<E>(title)`
+// from its `main`.
+
+use winit::{
+    event::{self, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+};
+
+#[path = "text_overlay.rs"]
+mod text_overlay;
+
+use text_overlay::{Section, Text, TextOverlay};
+
+/// Which `shaderc` pipeline stage a GLSL source string should be compiled for.
+#[derive(Clone, Copy)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+/// Compile `code` to SPIR-V with `shaderc` and wrap it as a `ShaderModuleSource`.
+///
+/// Examples that have switched their shaders over to `wgpu::include_spirv!`
+/// no longer need this at all; it stays around for the ones that haven't.
+pub fn load_glsl(code: &str, stage: ShaderStage) -> wgpu::ShaderModuleSource<'static> {
+    let ty = match stage {
+        ShaderStage::Vertex => shaderc::ShaderKind::Vertex,
+        ShaderStage::Fragment => shaderc::ShaderKind::Fragment,
+        ShaderStage::Compute => shaderc::ShaderKind::Compute,
+    };
+
+    let mut compiler = shaderc::Compiler::new().unwrap();
+    let binary = compiler
+        .compile_into_spirv(code, ty, "shader", "main", None)
+        .unwrap();
+    wgpu::ShaderModuleSource::SpirV(binary.as_binary().to_vec().leak())
+}
+
+/// What every example implements. The framework drives `init`/`resize`/
+/// `update`/`render`; an example's own draw call is whatever `render`
+/// records, same as before this trait grew a HUD.
+pub trait Example: 'static + Sized {
+    fn init(
+        sc_desc: &wgpu::SwapChainDescriptor,
+        device: &wgpu::Device,
+    ) -> (Self, Option<wgpu::CommandBuffer>);
+
+    fn update(&mut self, event: WindowEvent);
+
+    fn resize(
+        &mut self,
+        sc_desc: &wgpu::SwapChainDescriptor,
+        device: &wgpu::Device,
+    ) -> Option<wgpu::CommandBuffer>;
+
+    fn render(
+        &mut self,
+        frame: &wgpu::SwapChainOutput,
+        device: &wgpu::Device,
+        staging_belt: &mut wgpu::util::StagingBelt,
+    ) -> wgpu::CommandBuffer;
+
+    /// Text to draw as a HUD over this frame's output, composited by the
+    /// framework in a second pass after `render` returns. Examples that
+    /// don't need a HUD can leave the default of no overlay; ones that do
+    /// (an FPS counter, a live particle count, ...) only need to return a
+    /// string here instead of owning a `TextOverlay`/`StagingBelt`
+    /// themselves.
+    fn hud_text(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Set up a window, device, and swap chain, then drive `E` until the window
+/// is closed.
+pub fn run<E: Example>(title: &str) {
+    let event_loop = EventLoop::new();
+    let window = winit::window::WindowBuilder::new()
+        .with_title(title)
+        .build(&event_loop)
+        .unwrap();
+
+    let instance = wgpu::Instance::new();
+    let surface = unsafe { instance.create_surface(&window) };
+    let adapter = futures::executor::block_on(instance.request_adapter(
+        &wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::Default,
+            compatible_surface: Some(&surface),
+        },
+    ))
+    .expect("no compatible graphics adapter found");
+
+    let (device, queue) = futures::executor::block_on(
+        adapter.request_device(&wgpu::DeviceDescriptor::default(), None),
+    )
+    .expect("failed to request device");
+
+    let size = window.inner_size();
+    let mut sc_desc = wgpu::SwapChainDescriptor {
+        usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+        format: wgpu::TextureFormat::Bgra8UnormSrgb,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Mailbox,
+    };
+    let mut swap_chain = device.create_swap_chain(&surface, &sc_desc);
+
+    let (mut example, init_command_buf) = E::init(&sc_desc, &device);
+    if let Some(init_command_buf) = init_command_buf {
+        queue.submit(&[init_command_buf]);
+    }
+
+    let mut text_overlay = TextOverlay::new(&device, sc_desc.format);
+    let mut staging_belt = wgpu::util::StagingBelt::new(1024);
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Poll;
+        match event {
+            event::Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                sc_desc.width = size.width;
+                sc_desc.height = size.height;
+                swap_chain = device.create_swap_chain(&surface, &sc_desc);
+                if let Some(command_buf) = example.resize(&sc_desc, &device) {
+                    queue.submit(&[command_buf]);
+                }
+            }
+            event::Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                *control_flow = ControlFlow::Exit;
+            }
+            event::Event::WindowEvent { event, .. } => {
+                example.update(event);
+            }
+            event::Event::RedrawRequested(_) => {
+                let frame = swap_chain
+                    .get_current_frame()
+                    .expect("failed to acquire next swap chain frame")
+                    .output;
+
+                staging_belt.recall();
+
+                let mut command_buffers = vec![example.render(&frame, &device, &mut staging_belt)];
+
+                if let Some(text) = example.hud_text() {
+                    let mut encoder =
+                        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+                    text_overlay.draw_text(Section {
+                        screen_position: (10.0, 10.0),
+                        text: vec![Text::new(&text)
+                            .with_scale(20.0)
+                            .with_color([1.0, 1.0, 1.0, 1.0])],
+                        ..Section::default()
+                    });
+                    text_overlay.draw(
+                        &device,
+                        &mut staging_belt,
+                        &mut encoder,
+                        &frame.view,
+                        sc_desc.width,
+                        sc_desc.height,
+                    );
+                    command_buffers.push(encoder.finish());
+                }
+
+                staging_belt.finish();
+                queue.submit(&command_buffers);
+            }
+            event::Event::MainEventsCleared => {
+                window.request_redraw();
+            }
+            _ => {}
+        }
+    });
+}
@@ -0,0 +1,215 @@
+// Reusable GPU particle-emitter building block shared by example showcases
+// that simulate a cloud of particles on the compute pipeline and render them
+// as instanced geometry (e.g. the boids flocking demo and a falling-snow
+// demo built the same way).
+
+use zerocopy::AsBytes;
+
+/// Emitter configuration a falling-snow-style kernel would expect as its
+/// uniform: a spawn point, per-axis spawn spread, a constant force (gravity,
+/// wind, ...), a min/max lifetime spread, and the running `time`/`dt`.
+/// Kernels with a different uniform layout (like the boids flocking rules)
+/// can ignore this type entirely and hand their own bytes to
+/// [`ParticleSystemDescriptor::config_data`] instead.
+#[repr(C)]
+#[derive(Clone, Copy, AsBytes)]
+pub struct ParticleConfig {
+    pub emitter_position: [f32; 3],
+    pub spawn_spread: [f32; 3],
+    pub force: [f32; 3],
+    pub lifetime_min: f32,
+    pub lifetime_max: f32,
+    pub time: f32,
+    pub dt: f32,
+}
+
+/// Everything a caller supplies to stand up a [`ParticleSystem`]: how many
+/// particles to simulate, the kernel that advances them, the initial bytes
+/// for the kernel's config uniform and particle storage, and the instance
+/// vertex layout the particle storage should be read back as for rendering.
+pub struct ParticleSystemDescriptor<'a> {
+    pub particle_count: u32,
+    pub particles_per_group: u32,
+    pub compute_module: &'a wgpu::ShaderModule,
+    pub config_data: &'a [u8],
+    pub initial_particle_data: &'a [u8],
+    pub instance_attributes: &'a [wgpu::VertexAttributeDescriptor],
+    pub instance_stride: wgpu::BufferAddress,
+}
+
+/// Owns the double-buffered particle storage, the compute bind groups built
+/// around a caller-supplied kernel, and the per-frame dispatch.
+///
+/// The kernel is expected to bind `0` = config uniform, `1` = src particles,
+/// `2` = dst particles, and (for lifetime-driven emitters) to respawn a
+/// particle from the emitter once its per-particle lifetime, advanced by
+/// `dt`, has expired. The caller owns everything about rendering the result
+/// other than the storage buffer itself: build a render pipeline from
+/// `instance_attributes`/`instance_stride` and bind [`instance_buffer`](ParticleSystem::instance_buffer)
+/// as the per-instance vertex buffer.
+pub struct ParticleSystem {
+    config_buffer: wgpu::Buffer,
+    config_size: wgpu::BufferAddress,
+    particle_buffers: Vec<wgpu::Buffer>,
+    particle_bind_groups: Vec<wgpu::BindGroup>,
+    compute_pipeline: wgpu::ComputePipeline,
+    instance_attributes: Vec<wgpu::VertexAttributeDescriptor>,
+    instance_stride: wgpu::BufferAddress,
+    particle_count: u32,
+    work_group_count: u32,
+    frame_num: usize,
+}
+
+impl ParticleSystem {
+    pub fn new(device: &wgpu::Device, desc: &ParticleSystemDescriptor) -> Self {
+        let config_size = desc.config_data.len() as wgpu::BufferAddress;
+        let config_buffer = device.create_buffer_with_data(
+            desc.config_data,
+            wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+        );
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                bindings: &[
+                    wgpu::BindGroupLayoutBinding {
+                        binding: 0,
+                        visibility: wgpu::ShaderStage::COMPUTE,
+                        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                    },
+                    wgpu::BindGroupLayoutBinding {
+                        binding: 1,
+                        visibility: wgpu::ShaderStage::COMPUTE,
+                        ty: wgpu::BindingType::StorageBuffer {
+                            dynamic: false,
+                            readonly: false,
+                        },
+                    },
+                    wgpu::BindGroupLayoutBinding {
+                        binding: 2,
+                        visibility: wgpu::ShaderStage::COMPUTE,
+                        ty: wgpu::BindingType::StorageBuffer {
+                            dynamic: false,
+                            readonly: false,
+                        },
+                    },
+                ],
+            });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+        });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            layout: &pipeline_layout,
+            compute_stage: wgpu::ProgrammableStageDescriptor {
+                module: desc.compute_module,
+                entry_point: "main",
+            },
+        });
+
+        let particle_size = desc.initial_particle_data.len() as wgpu::BufferAddress;
+        let particle_buffers: Vec<wgpu::Buffer> = (0..2)
+            .map(|_| {
+                device.create_buffer_with_data(
+                    desc.initial_particle_data,
+                    wgpu::BufferUsage::VERTEX
+                        | wgpu::BufferUsage::STORAGE
+                        | wgpu::BufferUsage::COPY_DST,
+                )
+            })
+            .collect();
+
+        let particle_bind_groups: Vec<wgpu::BindGroup> = (0..2)
+            .map(|i| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &bind_group_layout,
+                    bindings: &[
+                        wgpu::Binding {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Buffer {
+                                buffer: &config_buffer,
+                                range: 0..config_size,
+                            },
+                        },
+                        wgpu::Binding {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Buffer {
+                                buffer: &particle_buffers[i],
+                                range: 0..particle_size,
+                            },
+                        },
+                        wgpu::Binding {
+                            binding: 2,
+                            resource: wgpu::BindingResource::Buffer {
+                                buffer: &particle_buffers[(i + 1) % 2], // bind to opposite buffer
+                                range: 0..particle_size,
+                            },
+                        },
+                    ],
+                })
+            })
+            .collect();
+
+        let work_group_count =
+            (desc.particle_count as f32 / desc.particles_per_group as f32).ceil() as u32;
+
+        ParticleSystem {
+            config_buffer,
+            config_size,
+            particle_buffers,
+            particle_bind_groups,
+            compute_pipeline,
+            instance_attributes: desc.instance_attributes.to_vec(),
+            instance_stride: desc.instance_stride,
+            particle_count: desc.particle_count,
+            work_group_count,
+            frame_num: 0,
+        }
+    }
+
+    /// Upload this frame's config uniform through a [`wgpu::util::StagingBelt`].
+    pub fn set_config(
+        &self,
+        belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        device: &wgpu::Device,
+        data: &[u8],
+    ) {
+        assert_eq!(data.len() as wgpu::BufferAddress, self.config_size);
+        belt.write_buffer(encoder, &self.config_buffer, 0, self.config_size, device)
+            .copy_from_slice(data);
+    }
+
+    /// Advance the simulation by one step, ping-ponging the src/dst storage
+    /// buffers so [`instance_buffer`](ParticleSystem::instance_buffer) always
+    /// returns the buffer the kernel just finished writing.
+    pub fn dispatch(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        {
+            let mut cpass = encoder.begin_compute_pass();
+            cpass.set_pipeline(&self.compute_pipeline);
+            cpass.set_bind_group(0, &self.particle_bind_groups[self.frame_num % 2], &[]);
+            cpass.dispatch(self.work_group_count, 1, 1);
+        }
+        self.frame_num += 1;
+    }
+
+    /// The buffer currently holding this frame's up-to-date particle data,
+    /// ready to be bound as an instance vertex buffer.
+    pub fn instance_buffer(&self) -> &wgpu::Buffer {
+        &self.particle_buffers[self.frame_num % 2]
+    }
+
+    /// The per-instance `VertexBufferDescriptor` this system's particle
+    /// storage should be bound as, built from the `instance_attributes` and
+    /// `instance_stride` passed to [`new`](ParticleSystem::new): feed this
+    /// into the caller's `RenderPipelineDescriptor::vertex_buffers`.
+    pub fn instance_buffer_descriptor(&self) -> wgpu::VertexBufferDescriptor<'_> {
+        wgpu::VertexBufferDescriptor {
+            stride: self.instance_stride,
+            step_mode: wgpu::InputStepMode::Instance,
+            attributes: &self.instance_attributes,
+        }
+    }
+
+    pub fn particle_count(&self) -> u32 {
+        self.particle_count
+    }
+}
@@ -0,0 +1,43 @@
+// Glyph-based text/HUD overlay for example showcases that want to display
+// debug labels (an FPS counter, a live particle count, ...) without each
+// reimplementing a text renderer on top of wgpu_glyph directly.
+
+pub use wgpu_glyph::{Section, Text};
+
+/// Rasterizes queued [`Section`]s with a bundled TTF and composes them in a
+/// final pass over the swap-chain view, drawn after the example's own pass.
+pub struct TextOverlay {
+    glyph_brush: wgpu_glyph::GlyphBrush<()>,
+}
+
+impl TextOverlay {
+    /// Build an overlay using the font bundled at `examples/DejaVuSansMono.ttf`.
+    pub fn new(device: &wgpu::Device, render_format: wgpu::TextureFormat) -> Self {
+        let font =
+            wgpu_glyph::ab_glyph::FontArc::try_from_slice(include_bytes!("DejaVuSansMono.ttf"))
+                .expect("bundled font is invalid");
+        let glyph_brush = wgpu_glyph::GlyphBrushBuilder::using_font(font).build(device, render_format);
+        TextOverlay { glyph_brush }
+    }
+
+    /// Queue a section of text to be drawn in the next [`draw`](TextOverlay::draw) call.
+    pub fn draw_text(&mut self, section: Section) {
+        self.glyph_brush.queue(section);
+    }
+
+    /// Rasterize and composite every section queued since the last call,
+    /// drawing directly into `view` without touching the depth buffer.
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) {
+        self.glyph_brush
+            .draw_queued(device, staging_belt, encoder, view, width, height)
+            .expect("glyph text render failed");
+    }
+}
@@ -0,0 +1,410 @@
+//! Crate root.
+//!
+//! This checkout only carries the slice of the crate that the work landed so
+//! far actually touches — buffer/shader utilities, macros, and push
+//! constants — plus the handful of core types they depend on. The
+//! adapter/surface/swap-chain layer, bind group and pipeline *creation*, and
+//! the rest of `RenderPass`'s and `ComputePass`'s recording methods (
+//! `set_pipeline`, `set_vertex_buffers`, `set_bind_group`, `draw`,
+//! `dispatch`, ...) live alongside the items below in the rest of this file
+//! and aren't reproduced here.
+
+mod macros;
+mod push_constants;
+pub mod util;
+
+pub use push_constants::PushConstantRange;
+
+pub type BufferAddress = u64;
+
+bitflags::bitflags! {
+    pub struct ShaderStage: u32 {
+        const VERTEX = 0x1;
+        const FRAGMENT = 0x2;
+        const COMPUTE = 0x4;
+    }
+}
+
+/// Vertex attribute scalar/vector formats. `size()` is `const fn` so
+/// [`vertex_attr_array!`](crate::vertex_attr_array) and
+/// [`util::vertex_stride`] can run in const context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexFormat {
+    Float,
+    Float2,
+    Float3,
+    Float4,
+    Uint,
+    Uint2,
+    Uint3,
+    Uint4,
+}
+
+impl VertexFormat {
+    pub const fn size(&self) -> BufferAddress {
+        match self {
+            VertexFormat::Float | VertexFormat::Uint => 4,
+            VertexFormat::Float2 | VertexFormat::Uint2 => 8,
+            VertexFormat::Float3 | VertexFormat::Uint3 => 12,
+            VertexFormat::Float4 | VertexFormat::Uint4 => 16,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VertexAttributeDescriptor {
+    pub offset: BufferAddress,
+    pub format: VertexFormat,
+    pub shader_location: u32,
+}
+
+pub enum ShaderModuleSource<'a> {
+    SpirV(&'a [u32]),
+    Wgsl(std::borrow::Cow<'a, str>),
+}
+
+bitflags::bitflags! {
+    pub struct BufferUsage: u32 {
+        const MAP_READ = 0x1;
+        const MAP_WRITE = 0x2;
+        const COPY_SRC = 0x4;
+        const COPY_DST = 0x8;
+        const INDEX = 0x10;
+        const VERTEX = 0x20;
+        const UNIFORM = 0x40;
+        const STORAGE = 0x80;
+    }
+}
+
+/// How long [`Device::poll`] should block waiting for queued async work
+/// (buffer mappings, ...) to complete.
+pub enum Maintain {
+    Wait,
+    Poll,
+}
+
+pub struct BufferDescriptor<'a> {
+    pub label: Option<&'a str>,
+    pub size: BufferAddress,
+    pub usage: BufferUsage,
+    /// If `true`, the buffer starts out host-mapped: its initial contents
+    /// can be written through [`Buffer::get_mapped_range_mut`] immediately,
+    /// with no `map_write_async` round-trip (and no wait for one) needed.
+    pub mapped_at_creation: bool,
+}
+
+pub struct BufferAsyncError;
+
+pub struct BufferWriteMapping<'a> {
+    pub data: &'a mut [u8],
+}
+
+/// A GPU-visible buffer. Cheaply `Clone`-able; clones share the same
+/// backend-side allocation, though only the instance a mapping was opened
+/// on can read it back through [`get_mapped_range_mut`](Buffer::get_mapped_range_mut).
+pub struct Buffer {
+    pub(crate) id: u64,
+    pub(crate) mapped_ptr: *mut u8,
+    pub(crate) mapped_len: BufferAddress,
+}
+
+impl Clone for Buffer {
+    fn clone(&self) -> Self {
+        Buffer {
+            id: self.id,
+            mapped_ptr: std::ptr::null_mut(),
+            mapped_len: 0,
+        }
+    }
+}
+
+impl Buffer {
+    /// This buffer's currently-mapped memory as a writable slice.
+    ///
+    /// Only valid while the buffer is actually host-mapped: immediately
+    /// after creation with `mapped_at_creation: true`, or once a pending
+    /// [`map_write_async`](Buffer::map_write_async) call's callback has run.
+    pub fn get_mapped_range_mut(&mut self) -> &mut [u8] {
+        assert!(!self.mapped_ptr.is_null(), "buffer is not currently mapped");
+        // SAFETY: `mapped_ptr`/`mapped_len` are only ever set, by
+        // `Device::create_buffer` or a `map_write_async` completion, to a
+        // pointer the backend has confirmed is host-mapped for exactly
+        // `mapped_len` bytes.
+        unsafe { std::slice::from_raw_parts_mut(self.mapped_ptr, self.mapped_len as usize) }
+    }
+
+    /// Asynchronously map this buffer for writing; `callback` runs once the
+    /// backend confirms the mapping (observed by the caller polling the
+    /// owning [`Device`]).
+    pub fn map_write_async<F>(&self, start: BufferAddress, size: BufferAddress, callback: F)
+    where
+        F: FnOnce(Result<BufferWriteMapping, BufferAsyncError>) + 'static,
+    {
+        unsafe extern "C" fn trampoline<F>(
+            status: i32,
+            data: *mut u8,
+            len: u64,
+            user_data: *mut std::ffi::c_void,
+        ) where
+            F: FnOnce(Result<BufferWriteMapping, BufferAsyncError>) + 'static,
+        {
+            let callback = Box::from_raw(user_data as *mut F);
+            let result = if status == 0 {
+                Ok(BufferWriteMapping {
+                    data: std::slice::from_raw_parts_mut(data, len as usize),
+                })
+            } else {
+                Err(BufferAsyncError)
+            };
+            callback(result);
+        }
+
+        let user_data = Box::into_raw(Box::new(callback)) as *mut std::ffi::c_void;
+        unsafe {
+            ffi::wgpu_buffer_map_write_async(self.id, start, size, trampoline::<F>, user_data);
+        }
+    }
+
+    pub fn unmap(&mut self) {
+        self.mapped_ptr = std::ptr::null_mut();
+        self.mapped_len = 0;
+        unsafe {
+            ffi::wgpu_buffer_unmap(self.id);
+        }
+    }
+}
+
+pub struct CommandEncoder {
+    pub(crate) id: u64,
+}
+
+impl CommandEncoder {
+    pub fn copy_buffer_to_buffer(
+        &mut self,
+        source: &Buffer,
+        source_offset: BufferAddress,
+        destination: &Buffer,
+        destination_offset: BufferAddress,
+        copy_size: BufferAddress,
+    ) {
+        unsafe {
+            ffi::wgpu_command_encoder_copy_buffer_to_buffer(
+                self.id,
+                source.id,
+                source_offset,
+                destination.id,
+                destination_offset,
+                copy_size,
+            );
+        }
+    }
+}
+
+/// A device's backend-reported limits. Only the push-constant limit is
+/// modeled here; the rest (max bind groups, max texture size, ...) live
+/// alongside the rest of `Device` elsewhere in this file.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub max_push_constant_size: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_push_constant_size: 0,
+        }
+    }
+}
+
+/// Opaque handle to a bind group layout, created by `Device::create_bind_group_layout`.
+pub struct BindGroupLayout {
+    pub(crate) id: u64,
+}
+
+pub struct PipelineLayoutDescriptor<'a> {
+    pub bind_group_layouts: &'a [&'a BindGroupLayout],
+    /// Push-constant ranges visible to pipelines built from this layout.
+    /// Validated against the device's `Limits::max_push_constant_size` by
+    /// [`Device::create_pipeline_layout`].
+    pub push_constant_ranges: &'a [PushConstantRange],
+}
+
+pub struct PipelineLayout {
+    pub(crate) id: u64,
+}
+
+/// A handle to a logical graphics+compute device. Buffer/texture/pipeline
+/// creation and submission live alongside `create_pipeline_layout` elsewhere
+/// in this file; only the push-constant-gating path is reproduced here.
+pub struct Device {
+    pub(crate) id: u64,
+    pub(crate) limits: Limits,
+}
+
+impl Device {
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Allocate a buffer. When `desc.mapped_at_creation` is set, the
+    /// returned `Buffer` is already host-mapped and readable/writable
+    /// through [`Buffer::get_mapped_range_mut`] before any submission.
+    pub fn create_buffer(&self, desc: &BufferDescriptor) -> Buffer {
+        let id = unsafe { ffi::wgpu_device_create_buffer(self.id, desc) };
+        let (mapped_ptr, mapped_len) = if desc.mapped_at_creation {
+            let mut ptr = std::ptr::null_mut();
+            unsafe {
+                ffi::wgpu_buffer_get_mapped_range(id, &mut ptr);
+            }
+            (ptr, desc.size)
+        } else {
+            (std::ptr::null_mut(), 0)
+        };
+        Buffer {
+            id,
+            mapped_ptr,
+            mapped_len,
+        }
+    }
+
+    /// Drive queued async work (buffer mappings, ...) forward. `Maintain::Wait`
+    /// blocks until everything outstanding has completed; `Maintain::Poll`
+    /// only advances whatever is already ready.
+    pub fn poll(&self, maintain: Maintain) {
+        unsafe {
+            ffi::wgpu_device_poll(self.id, matches!(maintain, Maintain::Wait));
+        }
+    }
+
+    /// Build a pipeline layout, asserting every push constant range fits
+    /// within this device's `max_push_constant_size` before handing the
+    /// ranges to the backend.
+    pub fn create_pipeline_layout(&self, desc: &PipelineLayoutDescriptor) -> PipelineLayout {
+        for range in desc.push_constant_ranges {
+            assert!(
+                range.range.end <= self.limits.max_push_constant_size,
+                "push constant range {:?} exceeds this device's max_push_constant_size of {}",
+                range.range,
+                self.limits.max_push_constant_size,
+            );
+        }
+        PipelineLayout {
+            id: unsafe { ffi::wgpu_device_create_pipeline_layout(self.id, desc) },
+        }
+    }
+}
+
+/// A render pass recorded into a `CommandEncoder`. `set_pipeline`,
+/// `set_vertex_buffers`, `set_bind_group` and `draw` live alongside
+/// `CommandEncoder::begin_render_pass` elsewhere in this file.
+pub struct RenderPass<'a> {
+    pub(crate) encoder_id: u64,
+    pub(crate) _marker: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> RenderPass<'a> {
+    /// Upload `data` into the active pipeline layout's push-constant storage
+    /// at `offset`, visible to `stages`. Both `offset` and `data.len()` must
+    /// be 4-byte aligned, the same alignment [`PushConstantRange::new`]
+    /// already enforces on the range this write falls inside.
+    pub fn set_push_constants(&mut self, stages: ShaderStage, offset: u32, data: &[u8]) {
+        assert_eq!(offset % 4, 0, "push constant offset is not 4-byte aligned");
+        assert_eq!(
+            data.len() % 4,
+            0,
+            "push constant data length is not 4-byte aligned"
+        );
+        unsafe {
+            ffi::wgpu_render_pass_set_push_constants(
+                self.encoder_id,
+                stages,
+                offset,
+                data.as_ptr(),
+                data.len() as u32,
+            );
+        }
+    }
+}
+
+/// A compute pass recorded into a `CommandEncoder`. `set_pipeline`,
+/// `set_bind_group` and `dispatch` live alongside
+/// `CommandEncoder::begin_compute_pass` elsewhere in this file.
+pub struct ComputePass<'a> {
+    pub(crate) encoder_id: u64,
+    pub(crate) _marker: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> ComputePass<'a> {
+    /// See [`RenderPass::set_push_constants`].
+    pub fn set_push_constants(&mut self, stages: ShaderStage, offset: u32, data: &[u8]) {
+        assert_eq!(offset % 4, 0, "push constant offset is not 4-byte aligned");
+        assert_eq!(
+            data.len() % 4,
+            0,
+            "push constant data length is not 4-byte aligned"
+        );
+        unsafe {
+            ffi::wgpu_compute_pass_set_push_constants(
+                self.encoder_id,
+                stages,
+                offset,
+                data.as_ptr(),
+                data.len() as u32,
+            );
+        }
+    }
+}
+
+/// Narrow FFI surface onto the native backend: only the calls needed by the
+/// items above are declared here, the rest of the crate's calls live
+/// alongside the rest of `Device`/`CommandEncoder`.
+mod ffi {
+    use super::{BufferDescriptor, PipelineLayoutDescriptor, ShaderStage};
+
+    pub(super) type MapWriteCallback = unsafe extern "C" fn(
+        status: i32,
+        data: *mut u8,
+        len: u64,
+        user_data: *mut std::ffi::c_void,
+    );
+
+    extern "C" {
+        pub(super) fn wgpu_device_create_pipeline_layout(
+            device_id: u64,
+            desc: *const PipelineLayoutDescriptor,
+        ) -> u64;
+        pub(super) fn wgpu_render_pass_set_push_constants(
+            pass_id: u64,
+            stages: ShaderStage,
+            offset: u32,
+            data: *const u8,
+            size: u32,
+        );
+        pub(super) fn wgpu_compute_pass_set_push_constants(
+            pass_id: u64,
+            stages: ShaderStage,
+            offset: u32,
+            data: *const u8,
+            size: u32,
+        );
+        pub(super) fn wgpu_device_create_buffer(device_id: u64, desc: *const BufferDescriptor) -> u64;
+        pub(super) fn wgpu_buffer_get_mapped_range(buffer_id: u64, out_ptr: *mut *mut u8);
+        pub(super) fn wgpu_buffer_map_write_async(
+            buffer_id: u64,
+            start: u64,
+            size: u64,
+            callback: MapWriteCallback,
+            user_data: *mut std::ffi::c_void,
+        );
+        pub(super) fn wgpu_buffer_unmap(buffer_id: u64);
+        pub(super) fn wgpu_device_poll(device_id: u64, force_wait: bool);
+        pub(super) fn wgpu_command_encoder_copy_buffer_to_buffer(
+            encoder_id: u64,
+            source_id: u64,
+            source_offset: u64,
+            destination_id: u64,
+            destination_offset: u64,
+            copy_size: u64,
+        );
+    }
+}
@@ -0,0 +1,55 @@
+/// Load a precompiled SPIR-V module at compile time.
+///
+/// The file is pulled in with `include_bytes!` and copied into a word-aligned
+/// static ([`util::WordAligned`]), so the 32-bit alignment assertions in
+/// [`util::make_spirv`] can never fire regardless of where the linker happens
+/// to place the raw bytes. The magic word is still checked when the macro's
+/// expansion runs, so a non-SPIR-V file is caught immediately rather than
+/// producing a module that fails to compile on the driver.
+///
+/// ```ignore
+/// let module = device.create_shader_module(&wgpu::include_spirv!("shader.vert.spv"));
+/// ```
+#[macro_export]
+macro_rules! include_spirv {
+    ($path:expr) => {{
+        static DATA: $crate::util::WordAligned<[u8; include_bytes!($path).len()]> =
+            $crate::util::WordAligned(*include_bytes!($path));
+        $crate::util::make_spirv(&DATA.0)
+    }};
+}
+
+/// Build a `&'static [VertexAttributeDescriptor]` without hand-computing offsets.
+///
+/// Each `location => format` pair is expanded in order, with every
+/// attribute's `offset` accumulated from the byte size of the formats that
+/// came before it, so getting one entry wrong can no longer silently corrupt
+/// the layout of the ones after it. Usable in const context, so the result
+/// can live in a `static` alongside the vertex buffer layout it describes;
+/// pair it with [`util::vertex_stride`](crate::util::vertex_stride) to get
+/// the buffer's `stride` from the same list of formats.
+///
+/// ```ignore
+/// static ATTRIBS: [wgpu::VertexAttributeDescriptor; 2] =
+///     wgpu::vertex_attr_array![0 => Float2, 1 => Float2];
+/// ```
+#[macro_export]
+macro_rules! vertex_attr_array {
+    ($($loc:expr => $format:ident),* $(,)?) => {
+        $crate::vertex_attr_array!(@internal 0; []; $($loc => $format),*)
+    };
+    (@internal $offset:expr; [$($head:expr,)*]; $loc:expr => $format:ident $(, $($tail:tt)*)?) => {
+        $crate::vertex_attr_array!(
+            @internal $offset + $crate::VertexFormat::$format.size();
+            [$($head,)* $crate::VertexAttributeDescriptor {
+                offset: $offset,
+                format: $crate::VertexFormat::$format,
+                shader_location: $loc,
+            },];
+            $($($tail)*)?
+        )
+    };
+    (@internal $offset:expr; [$($head:expr,)*];) => {
+        [$($head,)*]
+    };
+}
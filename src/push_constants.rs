@@ -0,0 +1,31 @@
+use std::ops::Range;
+
+/// A range of a pipeline layout's push-constant storage visible to a set of
+/// shader stages.
+///
+/// Passed via `PipelineLayoutDescriptor::push_constant_ranges` and written to
+/// with `RenderPass::set_push_constants` / `ComputePass::set_push_constants`,
+/// this lets small, frequently-changing per-draw data (e.g. a per-frame
+/// transform) skip the bind group + uniform buffer round-trip entirely.
+/// Ranges are gated behind the device's `max_push_constant_size` limit by
+/// `Device::create_pipeline_layout`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PushConstantRange {
+    /// Stages that can see this range when the pipeline layout is bound.
+    pub stages: super::ShaderStage,
+    /// Byte range within the pipeline layout's push-constant storage, must
+    /// be a multiple of 4 bytes at both ends.
+    pub range: Range<u32>,
+}
+
+impl PushConstantRange {
+    /// Build a range, asserting the 4-byte alignment push constants require
+    /// at both ends rather than deferring that check to validation deep
+    /// inside `create_pipeline_layout`.
+    pub fn new(stages: super::ShaderStage, range: Range<u32>) -> Self {
+        assert_eq!(range.start % 4, 0, "push constant range start is not 4-byte aligned");
+        assert_eq!(range.end % 4, 0, "push constant range end is not 4-byte aligned");
+        assert!(range.start < range.end, "push constant range is empty");
+        PushConstantRange { stages, range }
+    }
+}
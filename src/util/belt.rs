@@ -0,0 +1,164 @@
+use std::sync::mpsc;
+
+/// `copy_buffer_to_buffer` requires both offsets to be a multiple of this.
+const COPY_BUFFER_ALIGNMENT: super::BufferAddress = 4;
+
+fn align_copy_offset(offset: super::BufferAddress) -> super::BufferAddress {
+    (offset + COPY_BUFFER_ALIGNMENT - 1) / COPY_BUFFER_ALIGNMENT * COPY_BUFFER_ALIGNMENT
+}
+
+struct Chunk {
+    buffer: super::Buffer,
+    size: super::BufferAddress,
+    offset: super::BufferAddress,
+}
+
+/// Staging buffer management for uploading data to a GPU-visible buffer every
+/// frame without allocating a fresh staging `Buffer` for each write.
+///
+/// Internally it owns a ring of `MAP_WRITE | COPY_SRC` chunks, created
+/// host-mapped (`mapped_at_creation: true`) so a brand-new chunk is
+/// immediately writable with no async round-trip or blocking poll. A
+/// [`write_buffer`](StagingBelt::write_buffer) call sub-allocates from the
+/// currently open chunk (or maps a fresh one of at least `chunk_size` bytes
+/// once the current one is exhausted) and records a `copy_buffer_to_buffer`
+/// into the caller's target buffer. Call [`finish`](StagingBelt::finish)
+/// once per frame after all writes are queued, submit the encoder, then call
+/// [`recall`](StagingBelt::recall) to kick off re-mapping the chunks used
+/// this frame so they are ready again once the GPU is done with them.
+pub struct StagingBelt {
+    chunk_size: super::BufferAddress,
+    /// Chunks that still have room and are currently mapped.
+    active_chunks: Vec<Chunk>,
+    /// Chunks that are full, unmapped, and waiting on `recall` to be queued for re-mapping.
+    closed_chunks: Vec<Chunk>,
+    /// Chunks whose previous submission has finished and that have been re-mapped by `recall`.
+    free_chunks: Vec<Chunk>,
+    sender: mpsc::Sender<Chunk>,
+    receiver: mpsc::Receiver<Chunk>,
+}
+
+impl StagingBelt {
+    /// Create a new belt, with chunks allocated in units of `chunk_size` bytes.
+    ///
+    /// `chunk_size` should be large enough to contain the largest single
+    /// write you plan to make per frame; a write larger than the configured
+    /// chunk size allocates its own one-off chunk of exactly that size.
+    pub fn new(chunk_size: super::BufferAddress) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        StagingBelt {
+            chunk_size,
+            active_chunks: Vec::new(),
+            closed_chunks: Vec::new(),
+            free_chunks: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Sub-allocate `size` bytes from an open, mapped chunk, recording a copy
+    /// of those bytes into `target` at `offset`. Returns a writable slice of
+    /// the staging memory; write the data to upload into it before the
+    /// encoder is submitted.
+    pub fn write_buffer(
+        &mut self,
+        encoder: &mut super::CommandEncoder,
+        target: &super::Buffer,
+        offset: super::BufferAddress,
+        size: super::BufferAddress,
+        device: &super::Device,
+    ) -> &mut [u8] {
+        let chunk_index = match self
+            .active_chunks
+            .iter()
+            .position(|chunk| chunk.size - chunk.offset >= size)
+        {
+            Some(index) => index,
+            None => {
+                let free_index = self
+                    .free_chunks
+                    .iter()
+                    .position(|chunk| chunk.size >= size);
+                let chunk = match free_index {
+                    Some(index) => self.free_chunks.swap_remove(index),
+                    None => {
+                        let chunk_size = self.chunk_size.max(size);
+                        let buffer = device.create_buffer(&super::BufferDescriptor {
+                            label: Some("staging belt chunk"),
+                            size: chunk_size,
+                            usage: super::BufferUsage::MAP_WRITE | super::BufferUsage::COPY_SRC,
+                            mapped_at_creation: true,
+                        });
+                        Chunk {
+                            buffer,
+                            size: chunk_size,
+                            offset: 0,
+                        }
+                    }
+                };
+                self.active_chunks.push(chunk);
+                self.active_chunks.len() - 1
+            }
+        };
+
+        let chunk = &mut self.active_chunks[chunk_index];
+        encoder.copy_buffer_to_buffer(&chunk.buffer, chunk.offset, target, offset, size);
+        let byte_offset = chunk.offset;
+        // round the next write up to the backend's copy alignment so two
+        // back-to-back writes of a non-4-byte-multiple size never hand out
+        // a misaligned copy_buffer_to_buffer offset
+        chunk.offset = align_copy_offset(chunk.offset + size);
+
+        &mut chunk.buffer.get_mapped_range_mut()[byte_offset as usize..(byte_offset + size) as usize]
+    }
+
+    /// Unmap every chunk written to this frame so their contents are visible
+    /// to the GPU once the recorded command buffers are submitted. Call this
+    /// after all `write_buffer` calls for the frame and before `queue.submit`.
+    pub fn finish(&mut self) {
+        for mut chunk in self.active_chunks.drain(..) {
+            chunk.buffer.unmap();
+            self.closed_chunks.push(chunk);
+        }
+    }
+
+    /// Recall chunks that the GPU has finished reading from the last
+    /// submission, and kick off an asynchronous re-map of the chunks that
+    /// were closed by `finish` this frame so they can be reused later. A
+    /// chunk is only handed back out by `write_buffer` once its map-async
+    /// callback has confirmed the previous submission is done with it.
+    pub fn recall(&mut self) {
+        while let Ok(mut chunk) = self.receiver.try_recv() {
+            chunk.offset = 0;
+            self.free_chunks.push(chunk);
+        }
+
+        for chunk in self.closed_chunks.drain(..) {
+            let sender = self.sender.clone();
+            let size = chunk.size;
+            // clone the (cheaply-shared) buffer handle so we can both call
+            // map_write_async on it and move the owning `chunk` into the
+            // callback that hands it back once mapping completes.
+            chunk.buffer.clone().map_write_async(0, size, move |result| {
+                let mut chunk = chunk;
+                let mapping = result.expect("failed to map staging belt chunk");
+                chunk.buffer.mapped_ptr = mapping.data.as_mut_ptr();
+                chunk.buffer.mapped_len = mapping.data.len() as super::BufferAddress;
+                let _ = sender.send(chunk);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_copy_offset_rounds_up_to_four() {
+        assert_eq!(align_copy_offset(0), 0);
+        assert_eq!(align_copy_offset(1), 4);
+        assert_eq!(align_copy_offset(4), 4);
+        assert_eq!(align_copy_offset(5), 8);
+    }
+}
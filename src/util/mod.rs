@@ -1,6 +1,23 @@
+mod belt;
+
+pub use belt::StagingBelt;
+
 #[repr(align(4))]
 pub struct WordAligned<Bytes: ?Sized>(pub Bytes);
 
+/// Compute the natural stride (in bytes) of a vertex buffer whose attributes
+/// are tightly packed in the given format order, e.g. the same list of
+/// formats passed to [`vertex_attr_array!`](crate::vertex_attr_array).
+pub const fn vertex_stride(formats: &[super::VertexFormat]) -> super::BufferAddress {
+    let mut stride = 0;
+    let mut i = 0;
+    while i < formats.len() {
+        stride += formats[i].size();
+        i += 1;
+    }
+    stride
+}
+
 /// Treat the given by slice as a SPIR-V module.
 /// The pointer has to be aligned to 32-bit boundary and be a valid SPIR-V binary.
 pub fn make_spirv<'a>(data: &'a [u8]) -> super::ShaderModuleSource<'a> {
@@ -16,3 +33,23 @@ pub fn make_spirv<'a>(data: &'a [u8]) -> super::ShaderModuleSource<'a> {
     );
     super::ShaderModuleSource::SpirV(words)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertex_stride_sums_format_sizes() {
+        use super::super::VertexFormat::*;
+        assert_eq!(vertex_stride(&[Float2, Float2]), 16);
+        assert_eq!(vertex_stride(&[Float4]), 16);
+        assert_eq!(vertex_stride(&[]), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong magic word")]
+    fn make_spirv_rejects_bad_magic_word() {
+        let not_spirv = WordAligned([0u8; 8]);
+        make_spirv(&not_spirv.0);
+    }
+}